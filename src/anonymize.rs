@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use dicom::core::header::Header;
+use dicom::core::{DataElement, PrimitiveValue, Tag, VR};
+use dicom::dictionary_std::tags;
+use dicom::object::mem::InMemDicomObject;
+use dicom::object::FileDicomObject;
+use sha2::{Digest, Sha256};
+
+const DUMMY_NAME: &str = "ANONYMOUS";
+const DUMMY_DATE: &str = "19000101";
+const DUMMY_TIME: &str = "000000";
+
+/// PHI-bearing tags that are always de-identified, in addition to every
+/// top-level DA/DT/TM date-or-time element in the dataset. Elements nested
+/// inside sequence items are not visited.
+const PHI_TEXT_TAGS: &[Tag] = &[
+    tags::PATIENT_NAME,
+    tags::PATIENT_ID,
+    tags::INSTITUTION_NAME,
+    tags::REFERRING_PHYSICIAN_NAME,
+];
+const PHI_DATE_TAGS: &[Tag] = &[tags::PATIENT_BIRTH_DATE];
+
+/// Patient/study-identifying UIDs in the main data set that get remapped to
+/// a pseudonymous UID. Deliberately an allowlist rather than "every
+/// `VR::UI` element": the dataset also carries fixed, DICOM-registry UIDs
+/// (e.g. `SOPClassUID`) that identify the IOD itself, and remapping those
+/// would corrupt the file's type identification for downstream readers.
+/// Media Storage SOP Instance UID is the same kind of identifying UID but
+/// lives in the File Meta Information instead, so `anonymize` remaps it
+/// separately via `dicom.meta` rather than listing it here.
+const PHI_UID_TAGS: &[Tag] = &[
+    tags::STUDY_INSTANCE_UID,
+    tags::SERIES_INSTANCE_UID,
+    tags::SOP_INSTANCE_UID,
+    tags::FRAME_OF_REFERENCE_UID,
+];
+
+/// How a PHI value is replaced. UIDs are always deterministically remapped
+/// (see [`remap_uid`]) regardless of this setting, since study/series
+/// relationships must survive anonymization intact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum AnonymizeStrategy {
+    /// Replace the value with an empty string.
+    Blank,
+    /// Replace the value with a fixed placeholder.
+    Dummy,
+    /// Replace the value with a deterministic hash of the original.
+    Hash,
+}
+
+/// Hashes `value` with a fixed, version-stable algorithm (unlike
+/// `std::collections::hash_map::DefaultHasher`, whose output isn't
+/// guaranteed stable across Rust compiler versions) so the same original
+/// value maps to the same digest across separate runs and toolchains.
+fn digest64(value: &str) -> u64 {
+    let digest = Sha256::digest(value.as_bytes());
+    u64::from_be_bytes(digest[..8].try_into().unwrap())
+}
+
+fn hash_value(value: &str) -> String {
+    format!("{:016x}", digest64(value))
+}
+
+fn anonymized_value(strategy: AnonymizeStrategy, original: &str, dummy: &str) -> String {
+    match strategy {
+        AnonymizeStrategy::Blank => String::new(),
+        AnonymizeStrategy::Dummy => dummy.to_string(),
+        AnonymizeStrategy::Hash => hash_value(original),
+    }
+}
+
+/// Like `anonymized_value`, but for a DA/DT/TM-typed element: `Hash` can't
+/// just write `hash_value`'s digest into a date/time VR (it isn't a valid
+/// `YYYYMMDD`/`YYYYMMDDHHMMSS`/`HHMMSS`), so it instead maps the hash's bits
+/// into a validly-formatted pseudo-date/time, deterministic per `original`.
+fn anonymized_date_or_time(strategy: AnonymizeStrategy, original: &str, vr: VR, dummy: &str) -> String {
+    match strategy {
+        AnonymizeStrategy::Blank => String::new(),
+        AnonymizeStrategy::Dummy => dummy.to_string(),
+        AnonymizeStrategy::Hash => hashed_date_or_time(vr, original),
+    }
+}
+
+fn hashed_date_or_time(vr: VR, original: &str) -> String {
+    let digest = digest64(original);
+
+    let year = 1950 + (digest % 100);
+    let month = 1 + ((digest >> 8) % 12);
+    let day = 1 + ((digest >> 16) % 28); // valid in every month, including February
+    let hour = (digest >> 24) % 24;
+    let minute = (digest >> 32) % 60;
+    let second = (digest >> 40) % 60;
+
+    match vr {
+        VR::TM => format!("{:02}{:02}{:02}", hour, minute, second),
+        VR::DT => format!("{:04}{:02}{:02}{:02}{:02}{:02}", year, month, day, hour, minute, second),
+        _ => format!("{:04}{:02}{:02}", year, month, day),
+    }
+}
+
+/// Deterministically maps an original UID to a pseudonymous one, reusing the
+/// same replacement for a UID seen across multiple files so that study and
+/// series relationships stay intact after anonymization.
+fn remap_uid(uid_map: &Mutex<HashMap<String, String>>, original: &str) -> String {
+    let mut uid_map = uid_map.lock().unwrap();
+    if let Some(existing) = uid_map.get(original) {
+        return existing.clone();
+    }
+    let new_uid = format!("2.25.{}", u128::from_str_radix(&hash_value(original), 16).unwrap());
+    uid_map.insert(original.to_string(), new_uid.clone());
+    new_uid
+}
+
+fn replace_element(dicom: &mut FileDicomObject<InMemDicomObject>, tag: Tag, vr: VR, value: String) {
+    dicom.put(DataElement::new(tag, vr, PrimitiveValue::from(value)));
+}
+
+/// De-identifies a DICOM object in place, following `strategy` for PHI text
+/// and dates, and always remapping UIDs through the shared `uid_map`.
+pub fn anonymize(
+    dicom: &mut FileDicomObject<InMemDicomObject>,
+    strategy: AnonymizeStrategy,
+    uid_map: &Mutex<HashMap<String, String>>,
+) {
+    for &tag in PHI_TEXT_TAGS {
+        if let Ok(elem) = dicom.element(tag) {
+            let vr = elem.vr();
+            let original = elem.to_str().unwrap_or_default().into_owned();
+            let replacement = anonymized_value(strategy, &original, DUMMY_NAME);
+            replace_element(dicom, tag, vr, replacement);
+        }
+    }
+
+    for &tag in PHI_DATE_TAGS {
+        if let Ok(elem) = dicom.element(tag) {
+            let vr = elem.vr();
+            let original = elem.to_str().unwrap_or_default().into_owned();
+            let replacement = anonymized_date_or_time(strategy, &original, vr, DUMMY_DATE);
+            replace_element(dicom, tag, vr, replacement);
+        }
+    }
+
+    let date_time_tags: Vec<(Tag, VR)> = dicom
+        .iter()
+        .filter(|elem| matches!(elem.vr(), VR::DA | VR::DT | VR::TM))
+        .filter(|elem| !PHI_DATE_TAGS.contains(&elem.tag()))
+        .map(|elem| (elem.tag(), elem.vr()))
+        .collect();
+    for (tag, vr) in date_time_tags {
+        if let Ok(elem) = dicom.element(tag) {
+            let original = elem.to_str().unwrap_or_default().into_owned();
+            let dummy = if vr == VR::TM { DUMMY_TIME } else { DUMMY_DATE };
+            let replacement = anonymized_date_or_time(strategy, &original, vr, dummy);
+            replace_element(dicom, tag, vr, replacement);
+        }
+    }
+
+    for &tag in PHI_UID_TAGS {
+        if let Ok(elem) = dicom.element(tag) {
+            let original = elem.to_str().unwrap_or_default().into_owned();
+            let replacement = remap_uid(uid_map, &original);
+            replace_element(dicom, tag, VR::UI, replacement);
+        }
+    }
+
+    // Media Storage SOP Instance UID lives in the File Meta Information
+    // (group 0002), which `FileDicomObject` exposes as a typed
+    // `FileMetaTable` rather than through `dicom.element()`/`dicom.put()` --
+    // so it needs to be remapped directly, or `write_to_file` would carry
+    // the original, identifying UID straight into the anonymized file's
+    // header.
+    dicom.meta.media_storage_sop_instance_uid = remap_uid(uid_map, &dicom.meta.media_storage_sop_instance_uid);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dicom::object::meta::FileMetaTableBuilder;
+
+    fn test_object() -> FileDicomObject<InMemDicomObject> {
+        let meta = FileMetaTableBuilder::new()
+            .transfer_syntax("1.2.840.10008.1.2.1")
+            .media_storage_sop_class_uid("1.2.840.10008.5.1.4.1.1.7")
+            .media_storage_sop_instance_uid("1.2.3.4.5.6.media")
+            .build()
+            .unwrap();
+        let mut dicom = FileDicomObject::new_empty_with_meta(meta);
+        dicom.put(DataElement::new(tags::SOP_CLASS_UID, VR::UI, PrimitiveValue::from("1.2.840.10008.5.1.4.1.1.7")));
+        dicom.put(DataElement::new(tags::STUDY_INSTANCE_UID, VR::UI, PrimitiveValue::from("1.2.3.4.5.6.study")));
+        dicom.put(DataElement::new(tags::PATIENT_NAME, VR::PN, PrimitiveValue::from("Doe^John")));
+        dicom.put(DataElement::new(tags::PATIENT_BIRTH_DATE, VR::DA, PrimitiveValue::from("19700101")));
+        dicom.put(DataElement::new(tags::ACQUISITION_DATE, VR::DA, PrimitiveValue::from("20200101")));
+        dicom
+    }
+
+    #[test]
+    fn anonymized_value_blank_is_empty() {
+        assert_eq!(anonymized_value(AnonymizeStrategy::Blank, "original", "DUMMY"), "");
+    }
+
+    #[test]
+    fn anonymized_value_dummy_uses_the_placeholder() {
+        assert_eq!(anonymized_value(AnonymizeStrategy::Dummy, "original", "DUMMY"), "DUMMY");
+    }
+
+    #[test]
+    fn anonymized_value_hash_is_deterministic() {
+        let first = anonymized_value(AnonymizeStrategy::Hash, "original", "DUMMY");
+        let second = anonymized_value(AnonymizeStrategy::Hash, "original", "DUMMY");
+        assert_eq!(first, second);
+        assert_ne!(first, "original");
+    }
+
+    #[test]
+    fn hashed_date_or_time_produces_valid_da_dt_tm() {
+        for vr in [VR::DA, VR::DT, VR::TM] {
+            let value = hashed_date_or_time(vr, "some original value");
+            match vr {
+                VR::DA => assert_eq!(value.len(), 8),
+                VR::DT => assert_eq!(value.len(), 14),
+                VR::TM => assert_eq!(value.len(), 6),
+                _ => unreachable!(),
+            }
+            assert!(value.chars().all(|c| c.is_ascii_digit()));
+        }
+    }
+
+    #[test]
+    fn hashed_date_or_time_is_deterministic_per_original() {
+        let first = hashed_date_or_time(VR::DA, "2020-01-01 scan");
+        let second = hashed_date_or_time(VR::DA, "2020-01-01 scan");
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn remap_uid_reuses_the_same_replacement_for_a_repeated_uid() {
+        let uid_map = Mutex::new(HashMap::new());
+        let first = remap_uid(&uid_map, "1.2.3.4.5");
+        let second = remap_uid(&uid_map, "1.2.3.4.5");
+        assert_eq!(first, second);
+        assert_ne!(first, "1.2.3.4.5");
+        assert!(first.starts_with("2.25."));
+    }
+
+    #[test]
+    fn remap_uid_maps_distinct_uids_to_distinct_replacements() {
+        let uid_map = Mutex::new(HashMap::new());
+        let first = remap_uid(&uid_map, "1.2.3.4.5");
+        let second = remap_uid(&uid_map, "1.2.3.4.6");
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn anonymize_leaves_sop_class_uid_untouched() {
+        let mut dicom = test_object();
+        let uid_map = Mutex::new(HashMap::new());
+        anonymize(&mut dicom, AnonymizeStrategy::Dummy, &uid_map);
+
+        let sop_class_uid = dicom.element(tags::SOP_CLASS_UID).unwrap().to_str().unwrap().into_owned();
+        assert_eq!(sop_class_uid, "1.2.840.10008.5.1.4.1.1.7");
+    }
+
+    #[test]
+    fn anonymize_remaps_study_instance_uid_and_media_storage_sop_instance_uid() {
+        let mut dicom = test_object();
+        let uid_map = Mutex::new(HashMap::new());
+        let original_study_uid = dicom.element(tags::STUDY_INSTANCE_UID).unwrap().to_str().unwrap().into_owned();
+        let original_meta_uid = dicom.meta.media_storage_sop_instance_uid.clone();
+
+        anonymize(&mut dicom, AnonymizeStrategy::Dummy, &uid_map);
+
+        let new_study_uid = dicom.element(tags::STUDY_INSTANCE_UID).unwrap().to_str().unwrap().into_owned();
+        assert_ne!(new_study_uid, original_study_uid);
+        assert_ne!(dicom.meta.media_storage_sop_instance_uid, original_meta_uid);
+    }
+
+    #[test]
+    fn anonymize_blanks_patient_name_and_replaces_birth_date() {
+        let mut dicom = test_object();
+        let uid_map = Mutex::new(HashMap::new());
+        anonymize(&mut dicom, AnonymizeStrategy::Blank, &uid_map);
+
+        let patient_name = dicom.element(tags::PATIENT_NAME).unwrap().to_str().unwrap().into_owned();
+        assert_eq!(patient_name, "");
+        let birth_date = dicom.element(tags::PATIENT_BIRTH_DATE).unwrap().to_str().unwrap().into_owned();
+        assert_eq!(birth_date, "");
+    }
+}
@@ -0,0 +1,24 @@
+use dicom::core::Tag;
+use dicom::dictionary_std::tags;
+
+/// The default set of tags extracted from every DICOM file.
+///
+/// This is a small, commonly-useful subset of patient/study/series metadata.
+/// Callers that need a different set (e.g. vendor-specific tags) can override
+/// this via `ReadOptions::tags`.
+pub const DICOM_TAGS: &[Tag] = &[
+    tags::PATIENT_NAME,
+    tags::PATIENT_ID,
+    tags::PATIENT_BIRTH_DATE,
+    tags::PATIENT_SEX,
+    tags::STUDY_INSTANCE_UID,
+    tags::STUDY_DATE,
+    tags::STUDY_DESCRIPTION,
+    tags::SERIES_INSTANCE_UID,
+    tags::SERIES_DESCRIPTION,
+    tags::SOP_INSTANCE_UID,
+    tags::MODALITY,
+    tags::INSTANCE_NUMBER,
+    tags::ROWS,
+    tags::COLUMNS,
+];
@@ -1,14 +1,109 @@
 use crate::consts::DICOM_TAGS;
+use crate::dictionary::SupplementaryDictionary;
 use std::collections::HashMap;
 use std::path::PathBuf;
-use dicom::object::open_file;
+use dicom::object::{open_file, OpenFileOptions};
 use dicom::pixeldata::{PixelDecoder};
 use dicom::object::FileDicomObject;
-use dicom::core::Tag;
+use dicom::core::{Tag, VR};
 use dicom::object::mem::InMemDicomObject;
 use dicom::object::StandardDataDictionary;
 use dicom::core::dictionary::DataDictionary;
 
+/// Parses a tag in `GGGG,EEEE` hex notation (e.g. `"0008,0018"`).
+pub fn parse_tag(s: &str) -> Result<Tag, Box<dyn std::error::Error>> {
+    let (group, element) = s
+        .split_once(',')
+        .ok_or_else(|| format!("invalid tag '{}': expected GGGG,EEEE", s))?;
+    let group = u16::from_str_radix(group.trim(), 16)?;
+    let element = u16::from_str_radix(element.trim(), 16)?;
+    Ok(Tag(group, element))
+}
+
+/// Inclusive lower/upper bounds parsed from DICOM's native date range syntax
+/// `YYYYMMDD-YYYYMMDD` (either side may be omitted for an open-ended range).
+pub type DateRange = (Option<chrono::NaiveDate>, Option<chrono::NaiveDate>);
+
+/// Parses a DICOM date range string, e.g. `"20200101-20201231"`,
+/// `"-20201231"`, or `"20200101-"`.
+pub fn parse_date_range(s: &str) -> Result<DateRange, Box<dyn std::error::Error>> {
+    let (lower, upper) = s
+        .split_once('-')
+        .ok_or_else(|| format!("invalid date range '{}': expected YYYYMMDD-YYYYMMDD", s))?;
+    let lower = if lower.is_empty() { None } else { Some(parse_da(lower)?) };
+    let upper = if upper.is_empty() { None } else { Some(parse_da(upper)?) };
+    Ok((lower, upper))
+}
+
+/// Parses a DICOM `DA` value (`YYYYMMDD`).
+pub fn parse_da(s: &str) -> Result<chrono::NaiveDate, Box<dyn std::error::Error>> {
+    Ok(chrono::NaiveDate::parse_from_str(s.trim(), "%Y%m%d")?)
+}
+
+/// Parses a `--date-tag` value that may point at either a `DA` (`YYYYMMDD`)
+/// or a `DT` (`YYYYMMDD[HHMMSS[.FFFFFF]][&ZZXX]`) element, taking just the
+/// leading `YYYYMMDD` from the latter.
+pub fn parse_date_or_datetime(s: &str) -> Result<chrono::NaiveDate, Box<dyn std::error::Error>> {
+    let trimmed = s.trim();
+    if let Ok(date) = parse_da(trimmed) {
+        return Ok(date);
+    }
+    let prefix = trimmed
+        .get(0..8)
+        .ok_or_else(|| format!("invalid date '{}': expected YYYYMMDD or YYYYMMDDHHMMSS", s))?;
+    parse_da(prefix)
+}
+
+/// Controls how much of a DICOM file is read and which tags are extracted.
+///
+/// This lets callers that only care about metadata (e.g. directory-wide
+/// indexing) skip the cost of decoding pixel data entirely, and optionally
+/// stop parsing as soon as a given tag is reached, since DICOM elements are
+/// serialized in ascending tag order.
+#[derive(Debug, Clone, Default)]
+pub struct ReadOptions {
+    pub metadata_only: bool,
+    pub tags: Option<Vec<Tag>>,
+    pub stop_at_tag: Option<Tag>,
+    pub date_range: Option<DateRange>,
+    pub date_tag: Option<Tag>,
+    pub include_undated: bool,
+    pub dictionary: SupplementaryDictionary,
+}
+
+impl ReadOptions {
+    pub fn tags(&self) -> &[Tag] {
+        self.tags.as_deref().unwrap_or(DICOM_TAGS)
+    }
+
+    fn date_tag(&self) -> Tag {
+        self.date_tag.unwrap_or(dicom::dictionary_std::tags::STUDY_DATE)
+    }
+
+    /// Returns whether `dicom` falls inside the configured `date_range`. When
+    /// no range is configured, everything passes; when the target element is
+    /// missing or unparseable, the file is excluded unless `include_undated`
+    /// is set.
+    pub fn passes_date_filter(&self, dicom: &FileDicomObject<InMemDicomObject>) -> bool {
+        let Some((lower, upper)) = self.date_range else {
+            return true;
+        };
+
+        let date = dicom
+            .element(self.date_tag())
+            .ok()
+            .and_then(|elem| elem.to_str().ok())
+            .and_then(|s| parse_date_or_datetime(&s).ok());
+
+        match date {
+            Some(date) => {
+                lower.map_or(true, |lower| date >= lower) && upper.map_or(true, |upper| date <= upper)
+            }
+            None => self.include_undated,
+        }
+    }
+}
+
 pub fn read_dicom_file(
     filepath: &str,
 ) -> Result<
@@ -17,7 +112,22 @@ pub fn read_dicom_file(
     >,
     Box<dyn std::error::Error>,
 > {
-    let result = open_file(filepath);
+    read_dicom_file_with_options(filepath, &ReadOptions::default())
+}
+
+pub fn read_dicom_file_with_options(
+    filepath: &str,
+    options: &ReadOptions,
+) -> Result<
+    dicom::object::FileDicomObject<
+        dicom::object::InMemDicomObject<dicom::object::StandardDataDictionary>,
+    >,
+    Box<dyn std::error::Error>,
+> {
+    let result = match options.stop_at_tag {
+        Some(tag) => OpenFileOptions::new().read_until(tag).open_file(filepath),
+        None => open_file(filepath),
+    };
     match result {
         Ok(obj) => Ok(obj),
         Err(e) => {
@@ -27,8 +137,62 @@ pub fn read_dicom_file(
     }
 }
 
+/// A value extracted from a DICOM element.
+///
+/// DICOM elements can carry more than one value (VM > 1), e.g.
+/// `ImageOrientationPatient` has six decimal strings and `PixelSpacing` has
+/// two. The `Strings`/`Floats`/`Integers` variants preserve that structure
+/// instead of flattening it into a single backslash-joined string.
+#[derive(Debug, Clone)]
+pub enum Value {
+    String(String),
+    Strings(Vec<String>),
+    Integer(i64),
+    Integers(Vec<i64>),
+    Float(f64),
+    Floats(Vec<f64>),
+}
+
+/// Mirrors the EXIF-style display convention: a single joined, human-readable
+/// rendering, while the typed components remain available for programmatic
+/// use via the `Value` variants themselves.
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::String(s) => write!(f, "{}", s),
+            Value::Integer(i) => write!(f, "{}", i),
+            Value::Float(fl) => write!(f, "{}", fl),
+            Value::Strings(items) => write!(f, "{}", items.join(", ")),
+            Value::Integers(items) => {
+                let joined: Vec<String> = items.iter().map(|i| i.to_string()).collect();
+                write!(f, "{}", joined.join(", "))
+            }
+            Value::Floats(items) => {
+                let joined: Vec<String> = items.iter().map(|fl| fl.to_string()).collect();
+                write!(f, "{}", joined.join(", "))
+            }
+        }
+    }
+}
+
+impl serde::Serialize for Value {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Value::String(s) => serializer.serialize_str(s),
+            Value::Integer(i) => serializer.serialize_i64(*i),
+            Value::Float(fl) => serializer.serialize_f64(*fl),
+            Value::Strings(items) => items.serialize(serializer),
+            Value::Integers(items) => items.serialize(serializer),
+            Value::Floats(items) => items.serialize(serializer),
+        }
+    }
+}
+
 /// Represents the complete data extracted from a DICOM file.
-/// 
+///
 /// This struct contains all the relevant information from a DICOM file,
 /// including the file path, extracted tag values, and pixel data if available.
 /// The pixel data is stored as a multi-dimensional array of 32-bit signed integers,
@@ -37,35 +201,89 @@ pub fn read_dicom_file(
 #[derive(Debug, Clone)]
 pub struct DicomData {
     pub path: PathBuf,
-    pub tags: HashMap<String, String>,
+    pub tags: HashMap<String, Value>,
     pub pixel_data: Option<ndarray::Array<i32, ndarray::IxDyn>>,
 }
-    
 
-/// Extracts specific DICOM tags from a DICOM object and converts them to strings.
-/// 
-/// This function processes a list of DICOM tags and extracts their values from the provided
-/// DICOM object. All values are converted to strings regardless of their original DICOM type.
 
-fn extract_dicom_tags(dicom: &FileDicomObject<InMemDicomObject>, tags: &[Tag]) -> HashMap<String, String> {
-    // extract dicom tags and convert all values to strings
-    let mut tags_map: HashMap<String, String> = HashMap::new();
+/// Resolves `tags` to the same display names `extract_dicom_tags` would use,
+/// without requiring an open file. Tag-name resolution only depends on the
+/// tag code, not the element itself, so this lets callers that need a column
+/// or field set up front (e.g. a streaming CSV/Parquet writer) fix it before
+/// the first file is even read.
+pub fn tag_names(tags: &[Tag], dictionary: &SupplementaryDictionary) -> Vec<String> {
+    tags.iter().map(|tag| tag_name(*tag, dictionary)).collect()
+}
+
+fn tag_name(tag: Tag, dictionary: &SupplementaryDictionary) -> String {
+    dictionary
+        .get(tag)
+        .map(|entry| entry.alias.clone())
+        .or_else(|| StandardDataDictionary.by_tag(tag).map(|entry| entry.alias.to_string()))
+        .unwrap_or_else(|| format!("({:04X},{:04X})", tag.0, tag.1))
+}
+
+/// Extracts specific DICOM tags from a DICOM object, preserving value
+/// multiplicity (VM) instead of flattening multi-valued elements to a single
+/// backslash-joined string.
+///
+/// String-typed VRs are split on the DICOM backslash delimiter; numeric VRs
+/// use the multi-value accessors directly. Elements with exactly one value
+/// are stored as scalars so single-valued tags don't carry a needless
+/// one-element vector.
+///
+/// Tag names are resolved from `dictionary` first, so private/vendor tags
+/// can be given meaningful aliases, falling back to the standard DICOM
+/// dictionary and finally to the raw `(GGGG,EEEE)` hex so that distinct
+/// unknown tags don't collide under a generic name.
+fn extract_dicom_tags(
+    dicom: &FileDicomObject<InMemDicomObject>,
+    tags: &[Tag],
+    dictionary: &SupplementaryDictionary,
+    stop_at_tag: Option<Tag>,
+) -> HashMap<String, Value> {
+    let mut tags_map: HashMap<String, Value> = HashMap::new();
 
     for tag in tags {
-        // Use the standard dictionary to get the tag name
-        let tag_name = StandardDataDictionary.by_tag(*tag)
-            .map(|entry| entry.alias)
-            .unwrap_or("Unknown Tag");
-        
+        let tag_name = tag_name(*tag, dictionary);
+
         match dicom.element(*tag) {
             Ok(elem) => {
-                match elem.to_str() {
-                    Ok(s) => tags_map.insert(tag_name.to_string(), s.into_owned()),
-                    Err(_) => tags_map.insert(tag_name.to_string(), "<parse error>".to_string()),
+                let vr = match dictionary.get(*tag) {
+                    Some(entry) if elem.vr() == VR::UN => entry.vr,
+                    _ => elem.vr(),
                 };
+                let value = match vr {
+                    VR::IS => match elem.to_multi_int::<i64>() {
+                        Ok(values) => to_scalar_or_vec(values, Value::Integer, Value::Integers),
+                        Err(_) => Value::String("<parse error>".to_string()),
+                    },
+                    VR::US | VR::UL | VR::SS | VR::SL => match elem.to_multi_int::<i64>() {
+                        Ok(values) => to_scalar_or_vec(values, Value::Integer, Value::Integers),
+                        Err(_) => Value::String("<parse error>".to_string()),
+                    },
+                    VR::DS | VR::FL | VR::FD => match elem.to_multi_float64() {
+                        Ok(values) => to_scalar_or_vec(values, Value::Float, Value::Floats),
+                        Err(_) => Value::String("<parse error>".to_string()),
+                    },
+                    _ => match elem.to_str() {
+                        Ok(s) => {
+                            let values: Vec<String> = s.split('\\').map(|v| v.to_string()).collect();
+                            to_scalar_or_vec(values, Value::String, Value::Strings)
+                        }
+                        Err(_) => Value::String("<parse error>".to_string()),
+                    },
+                };
+                tags_map.insert(tag_name.to_string(), value);
+            }
+            Err(_) if stop_at_tag.is_some_and(|stop| *tag >= stop) => {
+                // Parsing halted at `stop_at_tag` before reaching this tag --
+                // expected and common, not a malformed file, so leave the
+                // column out rather than writing the library's "no such
+                // element" error as if it were a data value.
             }
             Err(e) => {
-                tags_map.insert(tag_name.to_string(), format!("{}", e));
+                tags_map.insert(tag_name.to_string(), Value::String(format!("{}", e)));
             }
         }
     }
@@ -73,6 +291,14 @@ fn extract_dicom_tags(dicom: &FileDicomObject<InMemDicomObject>, tags: &[Tag]) -
     tags_map
 }
 
+fn to_scalar_or_vec<T>(mut values: Vec<T>, scalar: impl Fn(T) -> Value, multi: impl Fn(Vec<T>) -> Value) -> Value {
+    if values.len() == 1 {
+        scalar(values.pop().unwrap())
+    } else {
+        multi(values)
+    }
+}
+
 /// Extracts and decodes pixel data from a DICOM object.
 /// 
 /// This function attempts to extract the pixel data from a DICOM file and convert it
@@ -155,9 +381,17 @@ fn extract_dicom_pixel_data(dicom: &FileDicomObject<InMemDicomObject>) -> Option
     }
 }
 
-pub fn extract_dicom_data(dicom: FileDicomObject<InMemDicomObject>, path: PathBuf) -> DicomData{
-    let tags_map = extract_dicom_tags(&dicom, DICOM_TAGS);
-    let pixel_data = extract_dicom_pixel_data(&dicom);
+pub fn extract_dicom_data_with_options(
+    dicom: FileDicomObject<InMemDicomObject>,
+    path: PathBuf,
+    options: &ReadOptions,
+) -> DicomData {
+    let tags_map = extract_dicom_tags(&dicom, options.tags(), &options.dictionary, options.stop_at_tag);
+    let pixel_data = if options.metadata_only {
+        None
+    } else {
+        extract_dicom_pixel_data(&dicom)
+    };
     DicomData {
         path,
         tags: tags_map,
@@ -165,3 +399,80 @@ pub fn extract_dicom_data(dicom: FileDicomObject<InMemDicomObject>, path: PathBu
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_tag_parses_well_formed_tag() {
+        assert_eq!(parse_tag("0010,0010").unwrap(), Tag(0x0010, 0x0010));
+    }
+
+    #[test]
+    fn parse_tag_rejects_missing_separator() {
+        assert!(parse_tag("00100010").is_err());
+    }
+
+    #[test]
+    fn parse_tag_rejects_non_hex_component() {
+        assert!(parse_tag("zzzz,0010").is_err());
+    }
+
+    #[test]
+    fn parse_da_parses_well_formed_date() {
+        assert_eq!(parse_da("20200101").unwrap(), chrono::NaiveDate::from_ymd_opt(2020, 1, 1).unwrap());
+    }
+
+    #[test]
+    fn parse_da_rejects_malformed_date() {
+        assert!(parse_da("not-a-date").is_err());
+    }
+
+    #[test]
+    fn parse_date_range_parses_closed_range() {
+        let (lower, upper) = parse_date_range("20200101-20201231").unwrap();
+        assert_eq!(lower, chrono::NaiveDate::from_ymd_opt(2020, 1, 1));
+        assert_eq!(upper, chrono::NaiveDate::from_ymd_opt(2020, 12, 31));
+    }
+
+    #[test]
+    fn parse_date_range_allows_open_lower_bound() {
+        let (lower, upper) = parse_date_range("-20201231").unwrap();
+        assert_eq!(lower, None);
+        assert_eq!(upper, chrono::NaiveDate::from_ymd_opt(2020, 12, 31));
+    }
+
+    #[test]
+    fn parse_date_range_allows_open_upper_bound() {
+        let (lower, upper) = parse_date_range("20200101-").unwrap();
+        assert_eq!(lower, chrono::NaiveDate::from_ymd_opt(2020, 1, 1));
+        assert_eq!(upper, None);
+    }
+
+    #[test]
+    fn parse_date_range_rejects_missing_separator() {
+        assert!(parse_date_range("20200101").is_err());
+    }
+
+    #[test]
+    fn parse_date_or_datetime_accepts_da() {
+        assert_eq!(
+            parse_date_or_datetime("20200101").unwrap(),
+            chrono::NaiveDate::from_ymd_opt(2020, 1, 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_date_or_datetime_accepts_dt_by_taking_leading_date() {
+        assert_eq!(
+            parse_date_or_datetime("20200101120000").unwrap(),
+            chrono::NaiveDate::from_ymd_opt(2020, 1, 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_date_or_datetime_rejects_too_short_value() {
+        assert!(parse_date_or_datetime("2020").is_err());
+    }
+}
+
@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use dicom::core::{Tag, VR};
+
+fn parse_vr(s: &str) -> Result<VR, Box<dyn std::error::Error>> {
+    use VR::*;
+    Ok(match s.trim().to_uppercase().as_str() {
+        "AE" => AE, "AS" => AS, "AT" => AT, "CS" => CS, "DA" => DA, "DS" => DS, "DT" => DT,
+        "FL" => FL, "FD" => FD, "IS" => IS, "LO" => LO, "LT" => LT, "OB" => OB, "OD" => OD,
+        "OF" => OF, "OL" => OL, "OW" => OW, "PN" => PN, "SH" => SH, "SL" => SL, "SQ" => SQ,
+        "SS" => SS, "ST" => ST, "TM" => TM, "UC" => UC, "UI" => UI, "UL" => UL, "UN" => UN,
+        "UR" => UR, "US" => US, "UT" => UT,
+        other => return Err(format!("unknown VR '{}'", other).into()),
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct DictionaryEntry {
+    pub vr: VR,
+    pub alias: String,
+}
+
+/// A user-supplied dictionary of private/vendor tags, loaded from a
+/// `group,element,VR,alias` CSV file (no header row) and consulted before
+/// the standard DICOM dictionary so private tags get meaningful aliases.
+#[derive(Debug, Clone, Default)]
+pub struct SupplementaryDictionary {
+    entries: HashMap<Tag, DictionaryEntry>,
+}
+
+impl SupplementaryDictionary {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut entries = HashMap::new();
+        let mut reader = csv::ReaderBuilder::new().has_headers(false).from_path(path)?;
+        for record in reader.records() {
+            let record = record?;
+            if record.len() < 4 {
+                return Err(format!("invalid dictionary row: {:?}", record).into());
+            }
+            let group = u16::from_str_radix(record[0].trim(), 16)?;
+            let element = u16::from_str_radix(record[1].trim(), 16)?;
+            let vr = parse_vr(&record[2])?;
+            let alias = record[3].trim().to_string();
+            entries.insert(Tag(group, element), DictionaryEntry { vr, alias });
+        }
+        Ok(Self { entries })
+    }
+
+    pub fn get(&self, tag: Tag) -> Option<&DictionaryEntry> {
+        self.entries.get(&tag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn write_csv(contents: &str) -> tempfile::NamedTempFile {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(contents.as_bytes()).unwrap();
+        file
+    }
+
+    #[test]
+    fn load_parses_valid_rows() {
+        let file = write_csv("0009,0010,LO,PrivateCreator\n0009,1001,SH,SiteSpecificId\n");
+        let dictionary = SupplementaryDictionary::load(file.path()).unwrap();
+
+        let entry = dictionary.get(Tag(0x0009, 0x0010)).unwrap();
+        assert_eq!(entry.vr, VR::LO);
+        assert_eq!(entry.alias, "PrivateCreator");
+        assert!(dictionary.get(Tag(0x0009, 0x9999)).is_none());
+    }
+
+    #[test]
+    fn load_rejects_unknown_vr() {
+        let file = write_csv("0009,0010,ZZ,PrivateCreator\n");
+        assert!(SupplementaryDictionary::load(file.path()).is_err());
+    }
+
+    #[test]
+    fn load_rejects_row_with_too_few_columns() {
+        let file = write_csv("0009,0010,LO\n");
+        assert!(SupplementaryDictionary::load(file.path()).is_err());
+    }
+
+    #[test]
+    fn load_rejects_non_hex_group_or_element() {
+        let file = write_csv("not-hex,0010,LO,PrivateCreator\n");
+        assert!(SupplementaryDictionary::load(file.path()).is_err());
+    }
+}
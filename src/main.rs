@@ -5,15 +5,33 @@ use indicatif::{ProgressBar, ProgressStyle};
 
 mod consts;
 mod os_utils;
-use os_utils::get_dicom_paths_from_folder;
+use os_utils::{discover_dicom_paths, read_paths_from_stdin, WalkOptions};
 mod dicom_utils;
-use dicom_utils::{read_dicom_file, extract_dicom_data, DicomData};
+use dicom_utils::{read_dicom_file, read_dicom_file_with_options, extract_dicom_data_with_options, parse_date_range, parse_tag, tag_names, DicomData, ReadOptions};
+mod anonymize;
+use anonymize::{anonymize, AnonymizeStrategy};
+mod output;
+use output::{ExportFormat, ResultWriter};
+mod dictionary;
+use dictionary::SupplementaryDictionary;
+mod pipeline;
+use pipeline::{run_receiver, WorkerResult};
+mod manifest;
+use manifest::{fold_into_manifest, merge_manifests, print_manifest_summary, StudyManifest};
+mod progress;
+use progress::ScanProgress;
+use std::collections::HashMap;
+use std::sync::Mutex;
 
 
-fn process_single_dicom(path: &PathBuf) -> Result<DicomData, Box<dyn std::error::Error + Send + Sync>> {
+fn process_single_dicom(path: &PathBuf, options: &ReadOptions, scan_progress: &ScanProgress) -> Result<Option<DicomData>, Box<dyn std::error::Error + Send + Sync>> {
     let path_str = path.to_str().unwrap();
-    
-    let dicom_obj = match read_dicom_file(path_str) {
+
+    if let Some(worker_index) = rayon::current_thread_index() {
+        scan_progress.set_worker_message(worker_index, path_str.to_string());
+    }
+
+    let dicom_obj = match read_dicom_file_with_options(path_str, options) {
         Ok(obj) => obj,
         Err(e) => {
             eprintln!("Failed to read DICOM file {}: {}", path_str, e);
@@ -23,9 +41,13 @@ fn process_single_dicom(path: &PathBuf) -> Result<DicomData, Box<dyn std::error:
             )));
         }
     };
-    
-    let dicom_data = extract_dicom_data(dicom_obj, path.clone());
-    Ok(dicom_data)
+
+    if !options.passes_date_filter(&dicom_obj) {
+        return Ok(None);
+    }
+
+    let dicom_data = extract_dicom_data_with_options(dicom_obj, path.clone(), options);
+    Ok(Some(dicom_data))
 }
 
 #[derive(Parser)]
@@ -39,21 +61,170 @@ struct Args {
 #[derive(Subcommand)]
 enum Command {
     Read(ReadArgs),
+    Anonymize(AnonymizeArgs),
 }
 
 #[derive(clap::Args)]
 struct ReadArgs {
+    #[arg(
+        short,
+        long,
+        required_unless_present = "from_stdin",
+        help = "Directory path to scan for files, or '-' to read a path list from stdin"
+    )]
+    path: Option<PathBuf>,
+    #[arg(short, long, help = "Number of threads to use for parallel processing (defaults to CPU cores - 1)")]
+    threads: Option<usize>,
+    #[arg(long, help = "Skip pixel data decoding and only extract metadata tags")]
+    metadata_only: bool,
+    #[arg(long, help = "Read a newline-separated path list from stdin instead of walking --path")]
+    from_stdin: bool,
+    #[arg(long, help = "With --from-stdin, paths are NUL-separated (pairs with `find -print0`/`fd -0`)")]
+    null_delimited: bool,
+    #[arg(long, value_delimiter = ';', help = "Whitelist of tags to extract, in GGGG,EEEE hex form, separated by ';' (overrides the built-in tag set)")]
+    tags: Option<Vec<String>>,
+    #[arg(long, help = "Stop parsing a file once a tag >= this one (GGGG,EEEE hex) is reached")]
+    stop_at_tag: Option<String>,
+    #[arg(short, long, help = "Path to write extracted tags to, in the format given by --format")]
+    output: Option<PathBuf>,
+    #[arg(short, long, value_enum, default_value_t = ExportFormat::Ndjson, help = "Format to write --output in")]
+    format: ExportFormat,
+    #[arg(long, help = "Only process files whose study date falls in this inclusive range, e.g. 20200101-20201231")]
+    date_range: Option<String>,
+    #[arg(long, help = "Tag to apply --date-range to, in GGGG,EEEE hex form (defaults to StudyDate)")]
+    date_tag: Option<String>,
+    #[arg(long, help = "With --date-range, include files whose date tag is missing or unparseable")]
+    include_undated: bool,
+    #[arg(long, help = "Supplementary dictionary CSV (group,element,VR,alias) for private/vendor tags")]
+    dictionary: Option<PathBuf>,
+    #[arg(long, help = "Print a Study/Series/Instance manifest summary after processing")]
+    manifest: bool,
+    #[arg(long, help = "Suppress progress bars (also disabled automatically when stdout isn't a TTY)")]
+    no_progress: bool,
+    #[arg(long, value_delimiter = ',', help = "Only scan files matching at least one of these globs, e.g. '*.dcm'")]
+    glob: Option<Vec<String>>,
+    #[arg(long, value_delimiter = ',', help = "Skip files matching any of these globs")]
+    exclude: Option<Vec<String>>,
+    #[arg(long, help = "Maximum directory depth to descend into")]
+    max_depth: Option<usize>,
+    #[arg(long, help = "Follow symlinks while walking the directory tree")]
+    follow_symlinks: bool,
+    #[arg(long, help = "Don't respect .gitignore/.dicomignore files found while walking")]
+    no_ignore_files: bool,
+    #[arg(long, help = "Peek for the DICM magic at byte offset 128 instead of scanning every file (useful when files have no extension)")]
+    detect_magic: bool,
+}
+
+impl ReadArgs {
+    fn read_options(&self) -> Result<ReadOptions, Box<dyn std::error::Error>> {
+        let tags = self
+            .tags
+            .as_ref()
+            .map(|tags| tags.iter().map(|t| parse_tag(t)).collect::<Result<Vec<_>, _>>())
+            .transpose()?;
+        let stop_at_tag = self.stop_at_tag.as_deref().map(parse_tag).transpose()?;
+        let date_range = self.date_range.as_deref().map(parse_date_range).transpose()?;
+        let date_tag = self.date_tag.as_deref().map(parse_tag).transpose()?;
+        let dictionary = self
+            .dictionary
+            .as_deref()
+            .map(SupplementaryDictionary::load)
+            .transpose()?
+            .unwrap_or_default();
+        Ok(ReadOptions {
+            metadata_only: self.metadata_only,
+            tags,
+            stop_at_tag,
+            date_range,
+            date_tag,
+            include_undated: self.include_undated,
+            dictionary,
+        })
+    }
+
+    fn walk_options(&self) -> WalkOptions {
+        WalkOptions {
+            globs: self.glob.clone().unwrap_or_default(),
+            excludes: self.exclude.clone().unwrap_or_default(),
+            max_depth: self.max_depth,
+            follow_symlinks: self.follow_symlinks,
+            respect_ignore_files: !self.no_ignore_files,
+            detect_magic: self.detect_magic,
+        }
+    }
+}
+
+#[derive(clap::Args)]
+struct AnonymizeArgs {
     #[arg(short, long, help = "Directory path to scan for files")]
     path: PathBuf,
+    #[arg(short, long, help = "Directory to write de-identified copies to, mirroring the input tree")]
+    output: PathBuf,
+    #[arg(short, long, value_enum, default_value_t = AnonymizeStrategy::Dummy, help = "How to replace PHI values (UIDs are always remapped consistently)")]
+    strategy: AnonymizeStrategy,
     #[arg(short, long, help = "Number of threads to use for parallel processing (defaults to CPU cores - 1)")]
     threads: Option<usize>,
+    #[arg(long, value_delimiter = ',', help = "Only scan files matching at least one of these globs, e.g. '*.dcm'")]
+    glob: Option<Vec<String>>,
+    #[arg(long, value_delimiter = ',', help = "Skip files matching any of these globs")]
+    exclude: Option<Vec<String>>,
+    #[arg(long, help = "Maximum directory depth to descend into")]
+    max_depth: Option<usize>,
+    #[arg(long, help = "Follow symlinks while walking the directory tree")]
+    follow_symlinks: bool,
+    #[arg(long, help = "Don't respect .gitignore/.dicomignore files found while walking")]
+    no_ignore_files: bool,
+    #[arg(long, help = "Peek for the DICM magic at byte offset 128 instead of scanning every file (useful when files have no extension)")]
+    detect_magic: bool,
+}
+
+impl AnonymizeArgs {
+    fn walk_options(&self) -> WalkOptions {
+        WalkOptions {
+            globs: self.glob.clone().unwrap_or_default(),
+            excludes: self.exclude.clone().unwrap_or_default(),
+            max_depth: self.max_depth,
+            follow_symlinks: self.follow_symlinks,
+            respect_ignore_files: !self.no_ignore_files,
+            detect_magic: self.detect_magic,
+        }
+    }
+}
+
+fn process_single_anonymize(
+    path: &PathBuf,
+    input_root: &PathBuf,
+    output_root: &PathBuf,
+    strategy: AnonymizeStrategy,
+    uid_map: &Mutex<HashMap<String, String>>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let path_str = path.to_str().unwrap();
+    let mut dicom_obj = read_dicom_file(path_str).map_err(|e| {
+        Box::new(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            format!("Failed to read DICOM file {}: {}", path_str, e),
+        )) as Box<dyn std::error::Error + Send + Sync>
+    })?;
+
+    anonymize(&mut dicom_obj, strategy, uid_map);
+
+    let relative_path = path.strip_prefix(input_root).unwrap_or(path);
+    let output_path = output_root.join(relative_path);
+    if let Some(parent) = output_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    dicom_obj.write_to_file(&output_path)?;
+    Ok(())
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
     match args.command {
         Command::Read(read_args) => {
-            println!("Processing DICOM files in: {}", read_args.path.display());
+            match &read_args.path {
+                Some(path) => println!("Processing DICOM files in: {}", path.display()),
+                None => println!("Reading DICOM file paths from stdin"),
+            }
             
             // Determine number of threads
             let num_cores = num_cpus::get();
@@ -74,50 +245,197 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .build_global()
                 .unwrap();
 
-            let dicom_paths = get_dicom_paths_from_folder(read_args.path.to_str().unwrap())?;
+            let read_options = read_args.read_options()?;
+
+            let use_stdin = read_args.from_stdin
+                || matches!(read_args.path.as_deref().and_then(|p| p.to_str()), Some("-"))
+                || read_args.path.is_none();
+            let dicom_paths = if use_stdin {
+                read_paths_from_stdin(read_args.null_delimited)?
+            } else {
+                discover_dicom_paths(read_args.path.as_deref().unwrap().to_str().unwrap(), &read_args.walk_options())?
+            };
             let total_files = dicom_paths.len();
             println!("Found {} DICOM files to process", total_files);
             
-            // Create a progress bar
+            // A top aggregate bar plus one spinner per worker thread showing
+            // the file it's currently parsing.
+            let scan_progress = ScanProgress::new(total_files, num_threads, read_args.no_progress);
+
+            // Rayon workers feed results over a bounded channel to a
+            // dedicated receiver thread, which keeps peak memory bounded
+            // instead of materializing every parsed file in one `Vec` before
+            // any of it can be used.
+            let (tx, rx) = crossbeam_channel::bounded::<WorkerResult>(4096);
+            let receiver_progress_bar = scan_progress.total_bar();
+
+            // Built before the scan starts and moved wholesale into the
+            // receiver thread below, so it's the sole writer of --output by
+            // construction -- no separate lock needed on top of that.
+            let result_writer = match &read_args.output {
+                Some(path) => {
+                    let columns = tag_names(read_options.tags(), &read_options.dictionary);
+                    Some(ResultWriter::create(path, read_args.format, columns)?)
+                }
+                None => None,
+            };
+
+            let want_manifest = read_args.manifest;
+
+            let receiver = std::thread::spawn(move || {
+                let mut result_writer = result_writer;
+                let (count, errors) = run_receiver(rx, receiver_progress_bar, |data| {
+                    if let Some(writer) = result_writer.as_mut() {
+                        if let Err(e) = writer.write(data) {
+                            eprintln!("Failed to write output record for {}: {}", data.path.display(), e);
+                        }
+                    }
+                });
+                if let Some(writer) = result_writer {
+                    if let Err(e) = writer.finish() {
+                        eprintln!("Failed to finalize output file: {}", e);
+                    }
+                }
+                (count, errors)
+            });
+
+            // Each rayon worker folds its own slice of paths into a
+            // thread-local manifest as it parses them, and `reduce` combines
+            // those manifests pairwise into one tree -- manifest bookkeeping
+            // stays parallel across workers instead of serializing through
+            // the single receiver thread that's already busy writing output.
+            let manifest = dicom_paths.into_par_iter().fold(StudyManifest::new, |mut manifest, path| {
+                let message = match process_single_dicom(&path, &read_options, &scan_progress) {
+                    Ok(Some(data)) => {
+                        if want_manifest {
+                            fold_into_manifest(&mut manifest, &data);
+                        }
+                        WorkerResult::Data(data)
+                    }
+                    Ok(None) => {
+                        scan_progress.total_bar().inc(1);
+                        return manifest;
+                    }
+                    Err(e) => WorkerResult::Error(e),
+                };
+                let _ = tx.send(message);
+                manifest
+            }).reduce(StudyManifest::new, merge_manifests);
+
+            // Drop the sender now that every worker is done so the receiver
+            // thread's channel iteration ends and it can be joined.
+            drop(tx);
+
+            let (count, errors) = receiver.join().expect("receiver thread panicked");
+
+            scan_progress.finish("Processing complete!");
+
+            if !errors.is_empty() {
+                println!("\nEncountered {} errors during processing:", errors.len());
+                for error in errors {
+                    eprintln!("Error: {}", error);
+                }
+            } else {
+                println!("\nAll DICOM files processed successfully!");
+            }
+
+            println!("Processing completed. Total files: {}", total_files);
+
+            if let Some(output_path) = &read_args.output {
+                println!("Wrote {} records to {}", count, output_path.display());
+            }
+
+            if read_args.manifest {
+                print_manifest_summary(&manifest);
+            }
+        }
+        Command::Anonymize(anonymize_args) => {
+            println!("Anonymizing DICOM files in: {}", anonymize_args.path.display());
+
+            let num_cores = num_cpus::get();
+            let num_threads = anonymize_args.threads.unwrap_or_else(|| {
+                if num_cores > 1 {
+                    num_cores - 1
+                } else {
+                    1
+                }
+            });
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build_global()
+                .unwrap();
+
+            let dicom_paths =
+                discover_dicom_paths(anonymize_args.path.to_str().unwrap(), &anonymize_args.walk_options())?;
+            let total_files = dicom_paths.len();
+            println!("Found {} DICOM files to anonymize", total_files);
+
             let progress_bar = ProgressBar::new(total_files as u64);
             progress_bar.set_style(
                 ProgressStyle::default_bar()
                     .template("[{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len}")
                     .unwrap()
-                    .progress_chars("#>-")
+                    .progress_chars("#>-"),
             );
-            
-            // Process files in parallel
-            let results: Vec<Result<DicomData, Box<dyn std::error::Error + Send + Sync>>> = dicom_paths
+
+            let uid_map: Mutex<HashMap<String, String>> = Mutex::new(HashMap::new());
+
+            let errors: Vec<_> = dicom_paths
                 .into_par_iter()
                 .map(|path| {
-                    let result = process_single_dicom(&path);
-                    
-                    // Update progress bar
+                    let result = process_single_anonymize(
+                        &path,
+                        &anonymize_args.path,
+                        &anonymize_args.output,
+                        anonymize_args.strategy,
+                        &uid_map,
+                    );
                     progress_bar.inc(1);
-                    
                     result
                 })
+                .filter_map(|r| r.err())
                 .collect();
-            
-            // Finish the progress bar
-            progress_bar.finish_with_message("Processing complete!");
-            
-            // Report any errors that occurred during processing
-            let errors: Vec<_> = results.into_iter().filter_map(|r| r.err()).collect();
+
+            progress_bar.finish_with_message("Anonymization complete!");
+
             if !errors.is_empty() {
-                println!("\nEncountered {} errors during processing:", errors.len());
+                println!("\nEncountered {} errors during anonymization:", errors.len());
                 for error in errors {
                     eprintln!("Error: {}", error);
                 }
             } else {
-                println!("\nAll DICOM files processed successfully!");
+                println!("\nAll DICOM files anonymized successfully!");
             }
-            
-            println!("Processing completed. Total files: {}", total_files);
         }
     }
     Ok(())
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dicom::core::Tag;
+
+    fn parse_read_args(args: &[&str]) -> ReadArgs {
+        let full_args: Vec<&str> = std::iter::once("fast_dicom_reader").chain(args.iter().copied()).collect();
+        match Args::try_parse_from(full_args).unwrap().command {
+            Command::Read(read_args) => read_args,
+            Command::Anonymize(_) => panic!("expected Read subcommand"),
+        }
+    }
+
+    #[test]
+    fn tags_flag_parses_a_single_tag() {
+        let read_args = parse_read_args(&["read", "--path", ".", "--tags", "0010,0010"]);
+        let options = read_args.read_options().unwrap();
+        assert_eq!(options.tags, Some(vec![Tag(0x0010, 0x0010)]));
+    }
+
+    #[test]
+    fn tags_flag_parses_multiple_semicolon_separated_tags() {
+        let read_args = parse_read_args(&["read", "--path", ".", "--tags", "0010,0010;0008,0018"]);
+        let options = read_args.read_options().unwrap();
+        assert_eq!(options.tags, Some(vec![Tag(0x0010, 0x0010), Tag(0x0008, 0x0018)]));
+    }
+}
 
@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::dicom_utils::{DicomData, Value};
+
+/// A single parsed instance within a series, as surfaced for manifest
+/// reconstruction.
+#[derive(Debug, Clone)]
+pub struct InstanceSummary {
+    pub path: PathBuf,
+    pub sop_instance_uid: String,
+    pub modality: String,
+    pub instance_number: Option<i64>,
+    pub rows: Option<i64>,
+    pub columns: Option<i64>,
+}
+
+pub type SeriesMap = HashMap<String, Vec<InstanceSummary>>;
+pub type StudyManifest = HashMap<String, SeriesMap>;
+
+fn value_string(data: &DicomData, tag_name: &str) -> Option<String> {
+    match data.tags.get(tag_name)? {
+        Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+fn value_int(data: &DicomData, tag_name: &str) -> Option<i64> {
+    match data.tags.get(tag_name)? {
+        Value::Integer(i) => Some(*i),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn summarize(data: &DicomData) -> Option<(String, String, InstanceSummary)> {
+    let study_uid = value_string(data, "StudyInstanceUID")?;
+    let series_uid = value_string(data, "SeriesInstanceUID")?;
+    let instance = InstanceSummary {
+        path: data.path.clone(),
+        sop_instance_uid: value_string(data, "SOPInstanceUID").unwrap_or_default(),
+        modality: value_string(data, "Modality").unwrap_or_default(),
+        instance_number: value_int(data, "InstanceNumber"),
+        rows: value_int(data, "Rows"),
+        columns: value_int(data, "Columns"),
+    };
+    Some((study_uid, series_uid, instance))
+}
+
+/// Folds a single `DicomData` into `manifest`, grouping it under its Study
+/// and Series UID. Called once per record inside each rayon worker's `fold`,
+/// so the manifest can be built up incrementally without retaining every
+/// `DicomData` for a second, bulk pass once the scan is done.
+pub fn fold_into_manifest(manifest: &mut StudyManifest, data: &DicomData) {
+    if let Some((study_uid, series_uid, instance)) = summarize(data) {
+        manifest.entry(study_uid).or_default().entry(series_uid).or_default().push(instance);
+    }
+}
+
+/// Merges `other` into `manifest`, concatenating instances of any series the
+/// two share. Used to combine the per-worker manifests built by a rayon
+/// `fold` into the single tree `reduce` produces.
+pub fn merge_manifests(mut manifest: StudyManifest, other: StudyManifest) -> StudyManifest {
+    for (study_uid, other_series) in other {
+        let series = manifest.entry(study_uid).or_default();
+        for (series_uid, mut instances) in other_series {
+            series.entry(series_uid).or_default().append(&mut instances);
+        }
+    }
+    manifest
+}
+
+fn instance_number_gaps(instances: &[InstanceSummary]) -> Vec<i64> {
+    let mut numbers: Vec<i64> = instances.iter().filter_map(|i| i.instance_number).collect();
+    numbers.sort_unstable();
+    numbers
+        .windows(2)
+        .flat_map(|pair| (pair[0] + 1)..pair[1])
+        .collect()
+}
+
+/// Prints a study count, series-per-study, instances-per-series and
+/// instance-number-gap report for `manifest`.
+pub fn print_manifest_summary(manifest: &StudyManifest) {
+    println!("\nManifest summary: {} studies", manifest.len());
+    for (study_uid, series) in manifest {
+        println!("  Study {} — {} series", study_uid, series.len());
+        for (series_uid, instances) in series {
+            let gaps = instance_number_gaps(instances);
+            print!("    Series {} — {} instances", series_uid, instances.len());
+            if gaps.is_empty() {
+                println!();
+            } else {
+                println!(" (InstanceNumber gaps: {:?})", gaps);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn dicom_data(study_uid: &str, series_uid: &str, instance_number: i64) -> DicomData {
+        let mut tags = HashMap::new();
+        tags.insert("StudyInstanceUID".to_string(), Value::String(study_uid.to_string()));
+        tags.insert("SeriesInstanceUID".to_string(), Value::String(series_uid.to_string()));
+        tags.insert("SOPInstanceUID".to_string(), Value::String(format!("{}.{}", series_uid, instance_number)));
+        tags.insert("Modality".to_string(), Value::String("CT".to_string()));
+        tags.insert("InstanceNumber".to_string(), Value::Integer(instance_number));
+        DicomData {
+            path: PathBuf::from(format!("{}.dcm", instance_number)),
+            tags,
+            pixel_data: None,
+        }
+    }
+
+    #[test]
+    fn instance_number_gaps_finds_missing_numbers() {
+        let instances: Vec<InstanceSummary> = [1, 2, 4, 7]
+            .iter()
+            .map(|&n| InstanceSummary {
+                path: PathBuf::from(format!("{}.dcm", n)),
+                sop_instance_uid: String::new(),
+                modality: String::new(),
+                instance_number: Some(n),
+                rows: None,
+                columns: None,
+            })
+            .collect();
+
+        assert_eq!(instance_number_gaps(&instances), vec![3, 5, 6]);
+    }
+
+    #[test]
+    fn instance_number_gaps_empty_for_contiguous_run() {
+        let instances: Vec<InstanceSummary> = [1, 2, 3]
+            .iter()
+            .map(|&n| InstanceSummary {
+                path: PathBuf::from(format!("{}.dcm", n)),
+                sop_instance_uid: String::new(),
+                modality: String::new(),
+                instance_number: Some(n),
+                rows: None,
+                columns: None,
+            })
+            .collect();
+
+        assert!(instance_number_gaps(&instances).is_empty());
+    }
+
+    #[test]
+    fn fold_into_manifest_accumulates_across_calls() {
+        let mut manifest = StudyManifest::new();
+        fold_into_manifest(&mut manifest, &dicom_data("study-1", "series-1", 1));
+        fold_into_manifest(&mut manifest, &dicom_data("study-1", "series-1", 2));
+        fold_into_manifest(&mut manifest, &dicom_data("study-1", "series-2", 1));
+        fold_into_manifest(&mut manifest, &dicom_data("study-2", "series-3", 1));
+
+        assert_eq!(manifest.len(), 2);
+        let study_1 = &manifest["study-1"];
+        assert_eq!(study_1.len(), 2);
+        assert_eq!(study_1["series-1"].len(), 2);
+        assert_eq!(study_1["series-2"].len(), 1);
+        assert_eq!(manifest["study-2"]["series-3"].len(), 1);
+    }
+
+    #[test]
+    fn fold_into_manifest_skips_results_missing_study_or_series_uid() {
+        let mut data = dicom_data("study-1", "series-1", 1);
+        data.tags.remove("SeriesInstanceUID");
+
+        let mut manifest = StudyManifest::new();
+        fold_into_manifest(&mut manifest, &data);
+        assert!(manifest.is_empty());
+    }
+
+    #[test]
+    fn merge_manifests_combines_shared_and_disjoint_studies() {
+        let mut a = StudyManifest::new();
+        fold_into_manifest(&mut a, &dicom_data("study-1", "series-1", 1));
+
+        let mut b = StudyManifest::new();
+        fold_into_manifest(&mut b, &dicom_data("study-1", "series-1", 2));
+        fold_into_manifest(&mut b, &dicom_data("study-2", "series-2", 1));
+
+        let merged = merge_manifests(a, b);
+
+        assert_eq!(merged["study-1"]["series-1"].len(), 2);
+        assert_eq!(merged["study-2"]["series-2"].len(), 1);
+    }
+}
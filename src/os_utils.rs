@@ -1,20 +1,225 @@
-use walkdir::WalkDir;
-use std::path::PathBuf;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
-pub fn get_dicom_paths_from_folder(
-    folder_path: &str,
+use ignore::{WalkBuilder, WalkState};
+
+/// Controls how `discover_dicom_paths` walks a directory tree.
+#[derive(Debug, Clone, Default)]
+pub struct WalkOptions {
+    /// Only files matching at least one of these globs are kept (empty means
+    /// everything matches).
+    pub globs: Vec<String>,
+    /// Files matching any of these globs are dropped, even if they matched
+    /// `globs`.
+    pub excludes: Vec<String>,
+    pub max_depth: Option<usize>,
+    pub follow_symlinks: bool,
+    /// Respect `.gitignore`-style ignore files, including `.dicomignore`.
+    pub respect_ignore_files: bool,
+    /// Peek for the `DICM` magic at byte offset 128 instead of accepting
+    /// every file, since many DICOM files carry no file extension.
+    pub detect_magic: bool,
+}
+
+/// Peeks for the `DICM` magic that DICOM's 128-byte preamble is followed by,
+/// so files with no extension are still recognized as DICOM.
+fn has_dicom_magic(path: &Path) -> bool {
+    let Ok(mut file) = std::fs::File::open(path) else {
+        return false;
+    };
+    let mut preamble = [0u8; 132];
+    file.read_exact(&mut preamble).is_ok() && &preamble[128..132] == b"DICM"
+}
+
+/// Recursively discovers candidate DICOM files under `root`, honoring
+/// `options`'s include/exclude globs, depth limit, symlink policy, and
+/// `.dicomignore`/`.gitignore` support.
+///
+/// Traversal uses the `ignore` crate's parallel walker -- the same one
+/// fd and ripgrep are built on -- so enumerating a huge archive isn't a
+/// serial bottleneck before parsing even starts.
+pub fn discover_dicom_paths(
+    root: &str,
+    options: &WalkOptions,
 ) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
-    Ok(WalkDir::new(folder_path)
-        .min_depth(1) // Skip the root directory itself
-        .into_iter()
-        .filter_map(|entry| entry.ok())
-        .filter(|entry| entry.file_type().is_file())
-        .map(|entry| entry.path().to_path_buf())
-        .filter(|path| {
-            path.file_name()
-                .and_then(|name| name.to_str())
-                .map_or(false, |name| name != ".DS_Store")
+    let mut override_builder = ignore::overrides::OverrideBuilder::new(root);
+    for glob in &options.globs {
+        override_builder.add(glob)?;
+    }
+    for exclude in &options.excludes {
+        override_builder.add(&format!("!{}", exclude))?;
+    }
+    let overrides = override_builder.build()?;
+
+    let mut builder = WalkBuilder::new(root);
+    builder
+        .overrides(overrides)
+        .follow_links(options.follow_symlinks)
+        .max_depth(options.max_depth)
+        .add_custom_ignore_filename(".dicomignore")
+        .git_ignore(options.respect_ignore_files)
+        .ignore(options.respect_ignore_files)
+        .git_global(false)
+        .git_exclude(false)
+        .hidden(false);
+    if options.respect_ignore_files {
+        builder.add_custom_ignore_filename(".dicomignore");
+    }
+
+    let paths: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+    builder.build_parallel().run(|| {
+        let paths = &paths;
+        let detect_magic = options.detect_magic;
+        Box::new(move |entry| {
+            if let Ok(entry) = entry {
+                let is_dicom_candidate = entry.file_type().is_some_and(|ft| ft.is_file())
+                    && entry
+                        .path()
+                        .file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| name != ".DS_Store")
+                    && (!detect_magic || has_dicom_magic(entry.path()));
+                if is_dicom_candidate {
+                    paths.lock().unwrap().push(entry.into_path());
+                }
+            }
+            WalkState::Continue
         })
-        .collect())
+    });
+
+    Ok(paths.into_inner().unwrap())
 }
 
+/// Reads a worklist of paths from standard input instead of walking a
+/// directory, so a pre-filtered list (e.g. only today's new studies) can be
+/// piped straight into the existing parallel parsing stage. Entries are
+/// separated by newlines, or by NUL bytes when `null_delimited` is set to
+/// pair with `find -print0`/`fd -0`; empty entries are skipped.
+pub fn read_paths_from_stdin(null_delimited: bool) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input)?;
+    Ok(parse_path_list(&input, null_delimited))
+}
+
+/// Splits `input` into paths on the delimiter `read_paths_from_stdin` uses,
+/// trimming a trailing `\r` and dropping empty entries. Factored out so the
+/// delimiter/empty-line handling can be tested without redirecting stdin.
+fn parse_path_list(input: &str, null_delimited: bool) -> Vec<PathBuf> {
+    let delimiter = if null_delimited { '\0' } else { '\n' };
+    input
+        .split(delimiter)
+        .map(|entry| entry.trim_end_matches('\r'))
+        .filter(|entry| !entry.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn touch(dir: &Path, relative: &str) {
+        let path = dir.join(relative);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).unwrap();
+        }
+        std::fs::write(path, b"").unwrap();
+    }
+
+    fn discover(dir: &Path, options: &WalkOptions) -> Vec<String> {
+        let mut names: Vec<String> = discover_dicom_paths(dir.to_str().unwrap(), options)
+            .unwrap()
+            .iter()
+            .map(|p| p.strip_prefix(dir).unwrap().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn discover_dicom_paths_finds_everything_by_default() {
+        let dir = tempfile::tempdir().unwrap();
+        touch(dir.path(), "a.dcm");
+        touch(dir.path(), "nested/b.dcm");
+
+        assert_eq!(discover(dir.path(), &WalkOptions::default()), vec!["a.dcm", "nested/b.dcm"]);
+    }
+
+    #[test]
+    fn discover_dicom_paths_respects_glob_whitelist() {
+        let dir = tempfile::tempdir().unwrap();
+        touch(dir.path(), "a.dcm");
+        touch(dir.path(), "b.txt");
+
+        let options = WalkOptions {
+            globs: vec!["*.dcm".to_string()],
+            ..WalkOptions::default()
+        };
+        assert_eq!(discover(dir.path(), &options), vec!["a.dcm"]);
+    }
+
+    #[test]
+    fn discover_dicom_paths_respects_exclude() {
+        let dir = tempfile::tempdir().unwrap();
+        touch(dir.path(), "a.dcm");
+        touch(dir.path(), "b.dcm");
+
+        let options = WalkOptions {
+            excludes: vec!["b.dcm".to_string()],
+            ..WalkOptions::default()
+        };
+        assert_eq!(discover(dir.path(), &options), vec!["a.dcm"]);
+    }
+
+    #[test]
+    fn discover_dicom_paths_respects_max_depth() {
+        let dir = tempfile::tempdir().unwrap();
+        touch(dir.path(), "a.dcm");
+        touch(dir.path(), "nested/b.dcm");
+
+        let options = WalkOptions {
+            max_depth: Some(1),
+            ..WalkOptions::default()
+        };
+        assert_eq!(discover(dir.path(), &options), vec!["a.dcm"]);
+    }
+
+    #[test]
+    fn discover_dicom_paths_respects_dicomignore_unless_no_ignore_files() {
+        let dir = tempfile::tempdir().unwrap();
+        touch(dir.path(), "a.dcm");
+        touch(dir.path(), "b.dcm");
+        std::fs::write(dir.path().join(".dicomignore"), "b.dcm\n").unwrap();
+
+        let respecting = WalkOptions {
+            respect_ignore_files: true,
+            ..WalkOptions::default()
+        };
+        assert!(!discover(dir.path(), &respecting).contains(&"b.dcm".to_string()));
+
+        let ignoring = WalkOptions {
+            respect_ignore_files: false,
+            ..WalkOptions::default()
+        };
+        assert!(discover(dir.path(), &ignoring).contains(&"b.dcm".to_string()));
+    }
+
+    #[test]
+    fn parse_path_list_splits_on_newlines_and_skips_empty_lines() {
+        let paths = parse_path_list("a.dcm\n\nb.dcm\r\n", false);
+        assert_eq!(paths, vec![PathBuf::from("a.dcm"), PathBuf::from("b.dcm")]);
+    }
+
+    #[test]
+    fn parse_path_list_splits_on_nul_when_null_delimited() {
+        let paths = parse_path_list("a.dcm\0b.dcm\0", true);
+        assert_eq!(paths, vec![PathBuf::from("a.dcm"), PathBuf::from("b.dcm")]);
+    }
+
+    #[test]
+    fn parse_path_list_does_not_split_newlines_when_null_delimited() {
+        let paths = parse_path_list("a.dcm\nb.dcm\0", true);
+        assert_eq!(paths, vec![PathBuf::from("a.dcm\nb.dcm")]);
+    }
+}
@@ -0,0 +1,225 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use serde_json::json;
+
+use crate::dicom_utils::DicomData;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    Ndjson,
+    Csv,
+    Parquet,
+}
+
+const PARQUET_BATCH_SIZE: usize = 1024;
+
+/// Streams each `DicomData` to `path`, in the format given by `format`, as it
+/// flows out of the parallel scan rather than waiting for the whole scan to
+/// finish.
+///
+/// NDJSON writes one JSON object per line as records arrive, so it composes
+/// directly with the buffering/streaming receiver. CSV and Parquet both need
+/// a fixed column set before the first row goes out, so both take `columns`
+/// up front -- the same tag list the scan itself was configured to extract --
+/// instead of discovering columns from the data after the fact. Parquet
+/// additionally batches rows in memory and only writes a `RecordBatch` every
+/// `PARQUET_BATCH_SIZE` rows, flushing whatever remains on `finish`.
+///
+/// Only the scan's single receiver thread ever calls `write`/`finish`; that's
+/// what keeps rows from interleaving, so `ResultWriter` doesn't need a lock
+/// of its own on top of it.
+pub struct ResultWriter {
+    columns: Vec<String>,
+    sink: Sink,
+}
+
+enum Sink {
+    Ndjson(BufWriter<File>),
+    Csv(csv::Writer<File>),
+    Parquet(ParquetSink),
+}
+
+impl ResultWriter {
+    pub fn create(
+        path: &Path,
+        format: ExportFormat,
+        columns: Vec<String>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let sink = match format {
+            ExportFormat::Ndjson => Sink::Ndjson(BufWriter::new(File::create(path)?)),
+            ExportFormat::Csv => {
+                let mut writer = csv::Writer::from_path(path)?;
+                let mut header = vec!["path".to_string()];
+                header.extend(columns.iter().cloned());
+                writer.write_record(&header)?;
+                Sink::Csv(writer)
+            }
+            ExportFormat::Parquet => Sink::Parquet(ParquetSink::create(path, &columns)?),
+        };
+        Ok(Self { columns, sink })
+    }
+
+    /// Writes a single record. Called once per `DicomData` as it arrives
+    /// from the parallel stage.
+    pub fn write(&mut self, data: &DicomData) -> Result<(), Box<dyn std::error::Error>> {
+        match &mut self.sink {
+            Sink::Ndjson(writer) => {
+                let record = json!({
+                    "path": data.path.display().to_string(),
+                    "tags": &data.tags,
+                    "pixel_shape": data.pixel_data.as_ref().map(|arr| arr.shape().to_vec()),
+                });
+                serde_json::to_writer(&mut *writer, &record)?;
+                writer.write_all(b"\n")?;
+            }
+            Sink::Csv(writer) => {
+                let mut row = vec![data.path.display().to_string()];
+                for column in &self.columns {
+                    row.push(data.tags.get(column).map(|v| v.to_string()).unwrap_or_default());
+                }
+                writer.write_record(&row)?;
+            }
+            Sink::Parquet(sink) => sink.push(data)?,
+        }
+        Ok(())
+    }
+
+    /// Flushes any buffered rows and closes the underlying file. Must be
+    /// called once the scan is done -- dropping a `ResultWriter` instead
+    /// silently loses Parquet's buffered tail batch.
+    pub fn finish(self) -> Result<(), Box<dyn std::error::Error>> {
+        match self.sink {
+            Sink::Ndjson(mut writer) => Ok(writer.flush()?),
+            Sink::Csv(mut writer) => Ok(writer.flush()?),
+            Sink::Parquet(sink) => sink.finish(),
+        }
+    }
+}
+
+struct ParquetSink {
+    columns: Vec<String>,
+    schema: Arc<arrow::datatypes::Schema>,
+    writer: parquet::arrow::ArrowWriter<File>,
+    paths: Vec<String>,
+    values: Vec<Vec<Option<String>>>,
+}
+
+impl ParquetSink {
+    fn create(path: &Path, columns: &[String]) -> Result<Self, Box<dyn std::error::Error>> {
+        use arrow::datatypes::{DataType, Field, Schema};
+        use parquet::arrow::ArrowWriter;
+
+        let mut fields = vec![Field::new("path", DataType::Utf8, false)];
+        fields.extend(columns.iter().map(|c| Field::new(c, DataType::Utf8, true)));
+        let schema = Arc::new(Schema::new(fields));
+
+        let file = File::create(path)?;
+        let writer = ArrowWriter::try_new(file, schema.clone(), None)?;
+
+        Ok(Self {
+            columns: columns.to_vec(),
+            schema,
+            writer,
+            paths: Vec::new(),
+            values: vec![Vec::new(); columns.len()],
+        })
+    }
+
+    fn push(&mut self, data: &DicomData) -> Result<(), Box<dyn std::error::Error>> {
+        self.paths.push(data.path.display().to_string());
+        for (column, values) in self.columns.iter().zip(self.values.iter_mut()) {
+            values.push(data.tags.get(column).map(|v| v.to_string()));
+        }
+        if self.paths.len() >= PARQUET_BATCH_SIZE {
+            self.flush_batch()?;
+        }
+        Ok(())
+    }
+
+    fn flush_batch(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        use arrow::array::StringArray;
+        use arrow::record_batch::RecordBatch;
+
+        if self.paths.is_empty() {
+            return Ok(());
+        }
+
+        let mut arrays: Vec<Arc<dyn arrow::array::Array>> =
+            vec![Arc::new(StringArray::from(std::mem::take(&mut self.paths)))];
+        for values in &mut self.values {
+            arrays.push(Arc::new(StringArray::from(std::mem::take(values))));
+        }
+
+        let batch = RecordBatch::try_new(self.schema.clone(), arrays)?;
+        self.writer.write(&batch)?;
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.flush_batch()?;
+        self.writer.close()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dicom_utils::Value;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    fn sample(path: &str, study: &str, instance_number: i64) -> DicomData {
+        let mut tags = HashMap::new();
+        tags.insert("StudyInstanceUID".to_string(), Value::String(study.to_string()));
+        tags.insert("InstanceNumber".to_string(), Value::Integer(instance_number));
+        DicomData {
+            path: PathBuf::from(path),
+            tags,
+            pixel_data: None,
+        }
+    }
+
+    #[test]
+    fn csv_write_aligns_header_and_row_values() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let columns = vec!["StudyInstanceUID".to_string(), "InstanceNumber".to_string()];
+        let mut writer = ResultWriter::create(file.path(), ExportFormat::Csv, columns).unwrap();
+        writer.write(&sample("a.dcm", "1.2.3", 1)).unwrap();
+        writer.write(&sample("b.dcm", "1.2.4", 2)).unwrap();
+        writer.finish().unwrap();
+
+        let mut reader = csv::Reader::from_path(file.path()).unwrap();
+        let header: Vec<String> = reader.headers().unwrap().iter().map(|s| s.to_string()).collect();
+        assert_eq!(header, vec!["path", "StudyInstanceUID", "InstanceNumber"]);
+
+        let records: Vec<csv::StringRecord> = reader.records().map(|r| r.unwrap()).collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].get(0).unwrap(), "a.dcm");
+        assert_eq!(records[0].get(1).unwrap(), "1.2.3");
+        assert_eq!(records[0].get(2).unwrap(), "1");
+        assert_eq!(records[1].get(0).unwrap(), "b.dcm");
+        assert_eq!(records[1].get(1).unwrap(), "1.2.4");
+        assert_eq!(records[1].get(2).unwrap(), "2");
+    }
+
+    #[test]
+    fn parquet_write_flushes_a_partial_batch_on_finish() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let columns = vec!["StudyInstanceUID".to_string()];
+        let mut writer = ResultWriter::create(file.path(), ExportFormat::Parquet, columns).unwrap();
+        assert!(PARQUET_BATCH_SIZE > 5, "test relies on writing fewer rows than a full batch");
+        for i in 0..5 {
+            writer.write(&sample(&format!("{}.dcm", i), "1.2.3", i)).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let reader = SerializedFileReader::new(File::open(file.path()).unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 5);
+    }
+}
@@ -0,0 +1,182 @@
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{Receiver, RecvTimeoutError};
+use indicatif::ProgressBar;
+
+use crate::dicom_utils::DicomData;
+
+/// Results are buffered for at most this many items...
+const MAX_BUFFER_LENGTH: usize = 1000;
+/// ...or this long, whichever comes first, before the receiver switches to
+/// streaming mode.
+const DEFAULT_MAX_BUFFER_TIME: Duration = Duration::from_millis(100);
+
+/// A single unit of work flowing from a rayon worker to the receiver thread.
+pub enum WorkerResult {
+    Data(DicomData),
+    Error(Box<dyn std::error::Error + Send + Sync>),
+}
+
+/// The receiver starts out `Buffering` so a fast scan can still report
+/// results in a stable, batched order; once the scan proves slow (either the
+/// buffer fills up or the time budget elapses) it switches to `Streaming` and
+/// every subsequent result is handled as soon as it arrives.
+enum ReceiverMode {
+    Buffering,
+    Streaming,
+}
+
+/// Drains `rx` until every sender has been dropped, handing each result to
+/// `on_result` as soon as the receiver is in streaming mode (or in a batch,
+/// for a scan that finished before the buffering deadline). Each `DicomData`
+/// is dropped as soon as `on_result` returns -- the receiver only keeps a
+/// running count, not the data itself, rather than getting a `Vec<DicomData>`
+/// back to process afterward. That's what keeps peak memory bounded on a
+/// tens-of-thousands-of-instances archive instead of retaining every parsed
+/// file (pixel data included) for the whole scan.
+pub fn run_receiver(
+    rx: Receiver<WorkerResult>,
+    progress_bar: ProgressBar,
+    mut on_result: impl FnMut(&DicomData),
+) -> (usize, Vec<Box<dyn std::error::Error + Send + Sync>>) {
+    let mut mode = ReceiverMode::Buffering;
+    let mut buffered = Vec::new();
+    let mut count = 0usize;
+    let mut errors = Vec::new();
+    let deadline = Instant::now() + DEFAULT_MAX_BUFFER_TIME;
+
+    loop {
+        let timeout = match mode {
+            ReceiverMode::Buffering => deadline.saturating_duration_since(Instant::now()),
+            ReceiverMode::Streaming => DEFAULT_MAX_BUFFER_TIME,
+        };
+
+        match rx.recv_timeout(timeout) {
+            Ok(WorkerResult::Data(result)) => {
+                progress_bar.inc(1);
+                match mode {
+                    ReceiverMode::Buffering => {
+                        buffered.push(result);
+                        if buffered.len() >= MAX_BUFFER_LENGTH {
+                            mode = ReceiverMode::Streaming;
+                            for buffered_result in buffered.drain(..) {
+                                on_result(&buffered_result);
+                                count += 1;
+                            }
+                        }
+                    }
+                    ReceiverMode::Streaming => {
+                        on_result(&result);
+                        count += 1;
+                    }
+                }
+            }
+            Ok(WorkerResult::Error(e)) => {
+                progress_bar.inc(1);
+                errors.push(e);
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                if matches!(mode, ReceiverMode::Buffering) {
+                    mode = ReceiverMode::Streaming;
+                    for buffered_result in buffered.drain(..) {
+                        on_result(&buffered_result);
+                        count += 1;
+                    }
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    // The scan finished before ever tripping the buffering deadline: sort
+    // the fully-buffered batch by path so its order is stable, then flush it.
+    buffered.sort_by(|a: &DicomData, b: &DicomData| a.path.cmp(&b.path));
+    for buffered_result in buffered.drain(..) {
+        on_result(&buffered_result);
+        count += 1;
+    }
+
+    (count, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    fn sample_data(name: &str) -> DicomData {
+        DicomData {
+            path: PathBuf::from(name),
+            tags: HashMap::new(),
+            pixel_data: None,
+        }
+    }
+
+    #[test]
+    fn run_receiver_flushes_the_buffer_once_the_scan_finishes_early() {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        tx.send(WorkerResult::Data(sample_data("a"))).unwrap();
+        tx.send(WorkerResult::Data(sample_data("b"))).unwrap();
+        drop(tx);
+
+        let mut seen = Vec::new();
+        let (count, errors) = run_receiver(rx, ProgressBar::hidden(), |data| {
+            seen.push(data.path.clone());
+        });
+
+        assert_eq!(count, 2);
+        assert_eq!(seen, vec![PathBuf::from("a"), PathBuf::from("b")]);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn run_receiver_switches_to_streaming_once_the_buffer_fills() {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        for i in 0..MAX_BUFFER_LENGTH {
+            tx.send(WorkerResult::Data(sample_data(&i.to_string()))).unwrap();
+        }
+        drop(tx);
+
+        let mut seen = 0usize;
+        let (count, errors) = run_receiver(rx, ProgressBar::hidden(), |_| seen += 1);
+
+        assert_eq!(count, MAX_BUFFER_LENGTH);
+        assert_eq!(seen, MAX_BUFFER_LENGTH);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn run_receiver_switches_to_streaming_after_the_buffering_timeout() {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        std::thread::spawn(move || {
+            tx.send(WorkerResult::Data(sample_data("early"))).unwrap();
+            std::thread::sleep(DEFAULT_MAX_BUFFER_TIME * 2);
+            tx.send(WorkerResult::Data(sample_data("late"))).unwrap();
+        });
+
+        let mut seen = Vec::new();
+        let (count, errors) = run_receiver(rx, ProgressBar::hidden(), |data| {
+            seen.push(data.path.clone());
+        });
+
+        assert_eq!(count, 2);
+        assert_eq!(seen, vec![PathBuf::from("early"), PathBuf::from("late")]);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn run_receiver_collects_errors_separately_from_on_result() {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        tx.send(WorkerResult::Data(sample_data("ok"))).unwrap();
+        tx.send(WorkerResult::Error(Box::new(std::io::Error::other("boom")))).unwrap();
+        drop(tx);
+
+        let mut seen = 0usize;
+        let (count, errors) = run_receiver(rx, ProgressBar::hidden(), |_| seen += 1);
+
+        assert_eq!(count, 1);
+        assert_eq!(seen, 1);
+        assert_eq!(errors.len(), 1);
+    }
+}
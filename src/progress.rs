@@ -0,0 +1,70 @@
+use std::io::IsTerminal;
+
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+
+/// A `MultiProgress` layout (borrowed from indicatif's cargo example): one
+/// aggregate bar tracking total files processed, plus one spinner per worker
+/// thread showing the file it's currently parsing.
+///
+/// Automatically disabled for `--no-progress` or when stdout isn't a TTY
+/// (e.g. output is redirected to a log).
+pub struct ScanProgress {
+    total: ProgressBar,
+    workers: Vec<ProgressBar>,
+}
+
+impl ScanProgress {
+    pub fn new(total_files: usize, num_workers: usize, no_progress: bool) -> Self {
+        let enabled = !no_progress && std::io::stdout().is_terminal();
+        let multi = MultiProgress::new();
+
+        let total = multi.add(ProgressBar::new(total_files as u64));
+        total.set_style(
+            ProgressStyle::default_bar()
+                .template("[{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} (ETA {eta})")
+                .unwrap()
+                .progress_chars("#>-"),
+        );
+
+        let workers: Vec<ProgressBar> = (0..num_workers)
+            .map(|i| {
+                let bar = multi.add(ProgressBar::new_spinner());
+                bar.set_style(ProgressStyle::default_spinner().template("  worker {prefix}: {spinner} {msg}").unwrap());
+                bar.set_prefix(i.to_string());
+                bar
+            })
+            .collect();
+
+        if !enabled {
+            total.set_draw_target(ProgressDrawTarget::hidden());
+            for worker in &workers {
+                worker.set_draw_target(ProgressDrawTarget::hidden());
+            }
+        }
+
+        Self { total, workers }
+    }
+
+    /// The aggregate bar tracking total files done. The receiver thread
+    /// advances it for every result it receives; files that a worker drops
+    /// without sending a result are counted by that worker instead.
+    pub fn total_bar(&self) -> ProgressBar {
+        self.total.clone()
+    }
+
+    /// Updates the spinner for the worker at `worker_index` to show the file
+    /// it's currently parsing. `worker_index` is expected to come from
+    /// `rayon::current_thread_index()`.
+    pub fn set_worker_message(&self, worker_index: usize, message: String) {
+        if let Some(bar) = self.workers.get(worker_index) {
+            bar.set_message(message);
+        }
+    }
+
+    pub fn finish(&self, message: &str) {
+        self.total.finish_with_message(message.to_string());
+        for worker in &self.workers {
+            worker.finish_and_clear();
+        }
+    }
+}